@@ -1,27 +1,169 @@
 use std::cmp::min;
 
+/// Where the per-pixel energy matrix comes from before each seam removal.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnergyBackend {
+    /// Recompute the dual-gradient energy on the CPU every iteration.
+    Cpu,
+    /// Energy is rendered on the GPU and handed to the algorithm via
+    /// `set_gpu_energy` before the next `remove_vertical_seam` call.
+    Gpu,
+}
+
+impl Default for EnergyBackend {
+    fn default() -> Self {
+        Self::Cpu
+    }
+}
+
+/// Which energy criterion `remove_vertical_seam` uses to pick a seam.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnergyMode {
+    /// Gradient-magnitude energy at the pixel itself (the classic criterion).
+    Backward,
+    /// Avidan-Shamir forward energy: the cost of the edges a seam removal
+    /// *creates*, which avoids some of the distortion backward energy causes
+    /// on structured images.
+    Forward,
+}
+
+impl Default for EnergyMode {
+    fn default() -> Self {
+        Self::Backward
+    }
+}
+
+/// Which neighbour of a removed seam pixel a forward-energy DP cell's
+/// minimum cost came from, so backtracking can follow the same path.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ForwardDir {
+    Left,
+    Up,
+    Right,
+}
+
 pub struct OriginalAlgo {
     pixels: Vec<[u8; 3]>,
     width: u32, // maybe have both of these as usize so we save a bunch of casts
     height: u32,
+    energy_backend: EnergyBackend,
+    energy_mode: EnergyMode,
+    gpu_energy: Option<Vec<u32>>,
 }
 
 impl OriginalAlgo {
     pub fn new(pixels: Vec<[u8; 3]>, width: u32, height: u32) -> Self {
+        Self::new_with_backend(pixels, width, height, EnergyBackend::Cpu)
+    }
+
+    pub fn new_with_backend(
+        pixels: Vec<[u8; 3]>,
+        width: u32,
+        height: u32,
+        energy_backend: EnergyBackend,
+    ) -> Self {
         Self {
             pixels,
             width,
             height,
+            energy_backend,
+            energy_mode: EnergyMode::Backward,
+            gpu_energy: None,
         }
     }
 
+    /// Supply an energy matrix computed by a GPU energy pass.
+    /// Must be called before `remove_vertical_seam` whenever `energy_backend`
+    /// is `EnergyBackend::Gpu` and the energy mode is `EnergyMode::Backward`;
+    /// the matrix is consumed by the next call. `EnergyMode::Forward` never
+    /// reads it and computes its transition costs from the pixels instead.
+    pub fn set_gpu_energy(&mut self, energy_matrix: Vec<u32>) {
+        self.gpu_energy = Some(energy_matrix);
+    }
+
+    pub fn energy_mode(&self) -> EnergyMode {
+        self.energy_mode
+    }
+
+    pub fn set_energy_mode(&mut self, energy_mode: EnergyMode) {
+        self.energy_mode = energy_mode;
+    }
+
+    /// The image's current (possibly already-carved) pixel grid, e.g. for a
+    /// GPU energy pass to re-upload before the next `remove_vertical_seam`.
+    pub fn pixels(&self) -> &[[u8; 3]] {
+        &self.pixels
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
     #[inline]
     fn index_of(&self, row: u32, col: u32) -> usize {
         (self.width * row + col) as usize
     }
 
     pub fn remove_vertical_seam(&mut self) -> Vec<u32> {
-        let energy_matrix = self.calculate_energy_matrix();
+        match self.energy_mode {
+            EnergyMode::Backward => self.remove_vertical_seam_backward(),
+            EnergyMode::Forward => self.remove_vertical_seam_forward(),
+        }
+    }
+
+    /// Remove one horizontal seam (one pixel per column, 8-connected
+    /// vertically) by transposing the image, delegating to the vertical-seam
+    /// machinery, then transposing back.
+    ///
+    /// The returned indices are in the *transposed* (column-major) flat
+    /// layout rather than this image's own row-major one: they are sorted
+    /// ascending by construction, so callers that need to re-apply the same
+    /// seam to a separately-held pixel buffer (e.g. `CarvingEngine`) should
+    /// transpose that buffer the same way before filtering by these indices.
+    pub fn remove_horizontal_seam(&mut self) -> Vec<u32> {
+        let (width, height) = (self.width, self.height);
+        let mut transposed = vec![[0u8; 3]; (width * height) as usize];
+        for row in 0..height {
+            for col in 0..width {
+                transposed[(col * height + row) as usize] =
+                    self.pixels[(row * width + col) as usize];
+            }
+        }
+
+        let mut sub =
+            OriginalAlgo::new_with_backend(transposed, height, width, self.energy_backend);
+        sub.set_energy_mode(self.energy_mode);
+        if let Some(gpu_energy) = self.gpu_energy.take() {
+            sub.set_gpu_energy(gpu_energy);
+        }
+        let removed_transposed = sub.remove_vertical_seam();
+
+        let new_height = height - 1;
+        let mut new_pixels = vec![[0u8; 3]; (width * new_height) as usize];
+        for row in 0..new_height {
+            for col in 0..width {
+                new_pixels[(row * width + col) as usize] =
+                    sub.pixels[(new_height * col + row) as usize];
+            }
+        }
+        self.pixels = new_pixels;
+        self.height = new_height;
+
+        removed_transposed
+    }
+
+    fn remove_vertical_seam_backward(&mut self) -> Vec<u32> {
+        let energy_matrix = match self.energy_backend {
+            EnergyBackend::Cpu => self.calculate_energy_matrix(),
+            EnergyBackend::Gpu => self
+                .gpu_energy
+                .take()
+                .expect("GPU energy backend selected but set_gpu_energy was never called"),
+        };
         let mut dp: Vec<u32> = Vec::with_capacity((self.width * self.height) as usize);
         dp.extend(&energy_matrix[0..self.width as usize]);
         for row in 1..self.height {
@@ -78,6 +220,93 @@ impl OriginalAlgo {
             }
         }
         to_remove.reverse();
+        self.apply_seam_removal(&to_remove);
+        to_remove
+    }
+
+    // Sum of absolute per-channel differences between two pixels, the cost
+    // unit the forward-energy transition costs are expressed in.
+    #[inline]
+    fn pixel_diff(&self, a: usize, b: usize) -> u32 {
+        let pa = self.pixels[a].map(|x| x as i32);
+        let pb = self.pixels[b].map(|x| x as i32);
+        ((pa[0] - pb[0]).abs() + (pa[1] - pb[1]).abs() + (pa[2] - pb[2]).abs()) as u32
+    }
+
+    fn remove_vertical_seam_forward(&mut self) -> Vec<u32> {
+        let size = (self.width * self.height) as usize;
+        let mut dp: Vec<u32> = vec![0; size];
+        let mut dir: Vec<ForwardDir> = vec![ForwardDir::Up; size];
+
+        for row in 1..self.height {
+            for col in 0..self.width {
+                let here = self.index_of(row, col);
+                let left_col = if col == 0 { 0 } else { col - 1 };
+                let right_col = if col == self.width - 1 {
+                    self.width - 1
+                } else {
+                    col + 1
+                };
+                let left = self.index_of(row, left_col);
+                let right = self.index_of(row, right_col);
+                let up = self.index_of(row - 1, col);
+
+                let c_u = self.pixel_diff(right, left);
+                let c_l = c_u + self.pixel_diff(up, left);
+                let c_r = c_u + self.pixel_diff(up, right);
+
+                let up_cost = dp[up] + c_u;
+                let mut best_cost = up_cost;
+                let mut best_dir = ForwardDir::Up;
+
+                if col != 0 {
+                    let left_cost = dp[self.index_of(row - 1, col - 1)] + c_l;
+                    if left_cost < best_cost {
+                        best_cost = left_cost;
+                        best_dir = ForwardDir::Left;
+                    }
+                }
+                if col != self.width - 1 {
+                    let right_cost = dp[self.index_of(row - 1, col + 1)] + c_r;
+                    if right_cost < best_cost {
+                        best_cost = right_cost;
+                        best_dir = ForwardDir::Right;
+                    }
+                }
+
+                dp[here] = best_cost;
+                dir[here] = best_dir;
+            }
+        }
+
+        let mut to_remove: Vec<u32> = Vec::with_capacity(self.height as usize);
+        let last_row_lo = self.index_of(self.height - 1, 0);
+        let last_row_hi = self.index_of(self.height - 1, self.width - 1);
+        let mut col = dp[last_row_lo..=last_row_hi]
+            .iter()
+            .enumerate()
+            .min_by_key(|(_i, en)| *en)
+            .map(|(i, _en)| i as u32)
+            .unwrap();
+        for row in (0..self.height).rev() {
+            to_remove.push(self.index_of(row, col) as u32);
+            if row == 0 {
+                break;
+            }
+            col = match dir[self.index_of(row, col)] {
+                ForwardDir::Left => col - 1,
+                ForwardDir::Up => col,
+                ForwardDir::Right => col + 1,
+            };
+        }
+        to_remove.reverse();
+        self.apply_seam_removal(&to_remove);
+        to_remove
+    }
+
+    // Delete the given flat pixel indices (one per row, in row order) and
+    // shrink `width` to match, shared by both energy-mode seam removals.
+    fn apply_seam_removal(&mut self, to_remove: &[u32]) {
         let mut k = 0;
         self.pixels = self
             .pixels
@@ -94,7 +323,6 @@ impl OriginalAlgo {
             .map(|(_i, x)| *x)
             .collect();
         self.width -= 1;
-        to_remove
     }
 
     fn calculate_energy_matrix(&self) -> Vec<u32> {
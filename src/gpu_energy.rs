@@ -0,0 +1,166 @@
+// GPU-accelerated replacement for `OriginalAlgo::calculate_energy_matrix`.
+// Renders the shared fullscreen-quad vertex shader with a fragment shader
+// that runs the shared `dual_gradient_energy` kernel and writes it into an
+// attached framebuffer/texture, then reads the result back through a
+// PIXEL_PACK_BUFFER PBO.
+use glow::{HasContext as _, NativeBuffer, NativeFramebuffer, NativeProgram, NativeTexture};
+
+use crate::shader_registry::{self, ShaderRegistry};
+
+const ENERGY_FRAGMENT: &str = r#"
+    precision mediump float;
+    in vec2 vUV;
+    out vec4 vFragColor;
+    uniform sampler2D textureMap;
+    uniform vec2 texelSize;
+
+    #include "dual_gradient_energy"
+
+    void main() {
+        float energy = dualGradientEnergy(textureMap, vUV, texelSize);
+        vFragColor = vec4(energy, 0.0, 0.0, 1.0);
+    }
+"#;
+
+pub struct GpuEnergyPass {
+    program: NativeProgram,
+    fbo: NativeFramebuffer,
+    energy_tex: NativeTexture,
+    pbo: NativeBuffer,
+    width: u32,
+    height: u32,
+}
+
+impl GpuEnergyPass {
+    pub fn new(gl: &glow::Context, width: u32, height: u32) -> Self {
+        let mut registry = ShaderRegistry::builtin();
+        let program = registry
+            .compile(
+                gl,
+                shader_registry::FULLSCREEN_QUAD_VERTEX_MAIN,
+                ENERGY_FRAGMENT,
+            )
+            .expect("built-in energy shader failed to compile");
+        unsafe {
+            // Float energy target so a single red channel can hold the full
+            // squared-gradient range without the 0..255 clamp a u8 texture
+            // would impose.
+            let energy_tex = gl.create_texture().expect("Failed to create texture");
+            gl.bind_texture(glow::TEXTURE_2D, Some(energy_tex));
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MIN_FILTER,
+                glow::NEAREST as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MAG_FILTER,
+                glow::NEAREST as i32,
+            );
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA32F as i32,
+                width as i32,
+                height as i32,
+                0,
+                glow::RGBA,
+                glow::FLOAT,
+                None,
+            );
+
+            let fbo = gl.create_framebuffer().expect("Failed to create FBO");
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(energy_tex),
+                0,
+            );
+            assert_eq!(
+                gl.check_framebuffer_status(glow::FRAMEBUFFER),
+                glow::FRAMEBUFFER_COMPLETE,
+                "Energy pass framebuffer is incomplete"
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+            let pbo = gl.create_buffer().expect("Failed to create PBO");
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(pbo));
+            gl.buffer_data_size(
+                glow::PIXEL_PACK_BUFFER,
+                (width * height * 4 * 4) as i32,
+                glow::STREAM_READ,
+            );
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+
+            Self {
+                program,
+                fbo,
+                energy_tex,
+                pbo,
+                width,
+                height,
+            }
+        }
+    }
+
+    /// Render the energy pass against `source_tex` and read the result back
+    /// into a `width * height` row-major matrix matching the layout
+    /// `OriginalAlgo::calculate_energy_matrix` produces on the CPU.
+    ///
+    /// `width`/`height` are the dimensions of `source_tex` *as currently
+    /// carved*, which shrink by one column every `step_gpu_precompute` call;
+    /// they're only bounded above by the `width`/`height` this pass was
+    /// constructed with (the FBO/texture/PBO capacity), not equal to them.
+    /// Using the construction-time size here instead would render and read
+    /// back the wrong sub-rectangle once the image has shrunk.
+    pub fn compute(
+        &self,
+        gl: &glow::Context,
+        source_tex: NativeTexture,
+        width: u32,
+        height: u32,
+    ) -> Vec<u32> {
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.use_program(Some(self.program));
+
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(source_tex));
+            gl.uniform_1_i32(
+                gl.get_uniform_location(self.program, "textureMap").as_ref(),
+                0,
+            );
+            gl.uniform_2_f32(
+                gl.get_uniform_location(self.program, "texelSize").as_ref(),
+                1.0 / width as f32,
+                1.0 / height as f32,
+            );
+
+            gl.draw_arrays(glow::TRIANGLES, 0, 6);
+
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(self.pbo));
+            gl.read_pixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                glow::RGBA,
+                glow::FLOAT,
+                glow::PixelPackData::BufferOffset(0),
+            );
+
+            let mut raw = vec![0u8; (width * height * 4 * 4) as usize];
+            gl.get_buffer_sub_data(glow::PIXEL_PACK_BUFFER, 0, &mut raw);
+
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+            raw.chunks_exact(16)
+                .map(|px| f32::from_ne_bytes([px[0], px[1], px[2], px[3]]) as u32)
+                .collect()
+        }
+    }
+}
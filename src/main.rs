@@ -4,12 +4,19 @@ use std::{
     time::Instant,
 };
 
-use algorithms::OriginalAlgo;
+use algorithms::{EnergyBackend, EnergyMode, OriginalAlgo};
 use eframe::egui::{self};
 use egui_glow::CallbackFn;
-use glow::{NativeBuffer, NativeShader, NativeTexture};
+use glow::{NativeBuffer, NativeProgram, NativeTexture};
+use gpu_energy::GpuEnergyPass;
 use image::DynamicImage;
+use post_process::ShaderChain;
 use rfd::FileDialog;
+use shader_registry::ShaderRegistry;
+
+mod gpu_energy;
+mod post_process;
+mod shader_registry;
 
 fn main() {
     let native_options = eframe::NativeOptions {
@@ -25,6 +32,149 @@ fn main() {
     );
 }
 
+// Delete the flat pixel at each of the first `num_seams` precomputed seams'
+// indices, same filtering pattern `OriginalAlgo::apply_seam_removal` uses.
+fn narrow_with_seams(
+    mut pixel_data: Vec<[u8; 3]>,
+    seams: &[Vec<u32>],
+    num_seams: u32,
+) -> Vec<[u8; 3]> {
+    for to_remove in &seams[0..num_seams as usize] {
+        let mut k = 0;
+        pixel_data = pixel_data
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _pix)| {
+                if k != to_remove.len() && *i == to_remove[k] as usize {
+                    k += 1;
+                    false
+                } else {
+                    true
+                }
+            })
+            .map(|(_i, pix)| pix)
+            .collect();
+    }
+    pixel_data
+}
+
+// Duplicate the pixel at each of the first `num_seams` precomputed seams'
+// indices, averaging it with its successor, to grow the image instead of
+// shrinking it.
+//
+// `seams[k]`'s flat indices were recorded by `OriginalAlgo` against the
+// width the image had when seam `k` was found, `original_width - k` (`k`
+// earlier seams had already been removed by then), but here each seam is
+// applied to a buffer that *grows* by one column per iteration instead.
+// Reusing those indices unadjusted drifts the duplicate left of its true
+// content-aware position by `2 * k` more columns on every successive row,
+// so remap each row's column by the number
+// of already-applied seams to its left in that same row first. This
+// mirrors the standard seam-insertion fix-up but isn't exact: it compares
+// against the *recorded* (pre-remap) columns of earlier seams rather than
+// their own remapped positions, which is the same approximation most
+// seam-carving references settle for rather than fully re-deriving each
+// seam's true original-image column.
+fn widen_with_seams(
+    mut pixel_data: Vec<[u8; 3]>,
+    seams: &[Vec<u32>],
+    num_seams: u32,
+    original_width: u32,
+) -> Vec<[u8; 3]> {
+    let mut applied_cols: Vec<Vec<u32>> = vec![Vec::new(); seams.first().map_or(0, Vec::len)];
+    for (seam_idx, to_insert) in seams[0..num_seams as usize].iter().enumerate() {
+        let width_at_find = original_width - seam_idx as u32;
+        let current_width = original_width + seam_idx as u32;
+        let mut remapped = Vec::with_capacity(to_insert.len());
+        for (row, &flat) in to_insert.iter().enumerate() {
+            let raw_col = flat - row as u32 * width_at_find;
+            let shift = applied_cols[row].iter().filter(|&&c| c <= raw_col).count() as u32;
+            applied_cols[row].push(raw_col);
+            remapped.push(row as u32 * current_width + raw_col + shift);
+        }
+
+        let mut k = 0;
+        let mut widened = Vec::with_capacity(pixel_data.len() + remapped.len());
+        for (i, pix) in pixel_data.iter().enumerate() {
+            widened.push(*pix);
+            if k != remapped.len() && i == remapped[k] as usize {
+                let neighbour = pixel_data.get(i + 1).copied().unwrap_or(*pix);
+                widened.push(average_pixels(*pix, neighbour));
+                k += 1;
+            }
+        }
+        pixel_data = widened;
+    }
+    pixel_data
+}
+
+fn average_pixels(a: [u8; 3], b: [u8; 3]) -> [u8; 3] {
+    [
+        ((a[0] as u16 + b[0] as u16) / 2) as u8,
+        ((a[1] as u16 + b[1] as u16) / 2) as u8,
+        ((a[2] as u16 + b[2] as u16) / 2) as u8,
+    ]
+}
+
+// Row-major (height rows of width cols) <-> column-major (width rows of
+// height cols) conversion, mirroring `OriginalAlgo::remove_horizontal_seam`'s
+// internal transpose so horizontal seams can be re-applied to a pixel
+// buffer `OriginalAlgo` doesn't own.
+fn transpose_pixels(pixels: &[[u8; 3]], width: u32, height: u32) -> Vec<[u8; 3]> {
+    let mut out = vec![[0u8; 3]; (width * height) as usize];
+    for row in 0..height {
+        for col in 0..width {
+            out[(col * height + row) as usize] = pixels[(row * width + col) as usize];
+        }
+    }
+    out
+}
+
+// Convert the first `num_seams` precomputed seams into normalized device
+// coordinates for the seam-overlay visualization, one (first, count) range
+// per seam so each draws as its own disconnected `LINE_STRIP`.
+//
+// Vertical seams hold one row-major flat index per row against that
+// iteration's width (`width - i`, since earlier removals already shrank it);
+// horizontal seams are stored in `remove_horizontal_seam`'s transposed
+// (column-major) layout against that iteration's height, so row/col are
+// recovered the other way around. See `OriginalAlgo::remove_horizontal_seam`
+// for the layout this mirrors.
+fn seam_overlay_points(
+    seams: &[Vec<u32>],
+    num_seams: u32,
+    width: u32,
+    height: u32,
+    horizontal: bool,
+) -> (Vec<[f32; 2]>, Vec<(i32, i32)>) {
+    let mut points = Vec::new();
+    let mut ranges = Vec::new();
+    for (i, seam) in seams.iter().take(num_seams as usize).enumerate() {
+        let first = points.len() as i32;
+        let (row_span, col_span) = if horizontal {
+            (height - i as u32, width)
+        } else {
+            (height, width - i as u32)
+        };
+        for &idx in seam {
+            let (row, col) = if horizontal {
+                (idx % row_span, idx / row_span)
+            } else {
+                (idx / col_span, idx % col_span)
+            };
+            // Normalize against the *fixed* image dimensions, not
+            // `row_span`/`col_span`: those shrink per iteration and are only
+            // the right denominator for decoding `idx` into (row, col), not
+            // for placing that (row, col) in the canvas's NDC space.
+            let x = (col as f32 / (width - 1).max(1) as f32) * 2.0 - 1.0;
+            let y = (row as f32 / (height - 1).max(1) as f32) * 2.0 - 1.0;
+            points.push([x, y]);
+        }
+        ranges.push((first, points.len() as i32 - first));
+    }
+    (points, ranges)
+}
+
 enum LoadStatus {
     NotLoaded,
     Loaded(String),
@@ -43,24 +193,59 @@ struct CarvingEngine {
     image: DynamicImage,
     algo: Arc<Mutex<OriginalAlgo>>,
     removed_seams: Arc<Mutex<Vec<Vec<u32>>>>,
+    algo_horizontal: Arc<Mutex<OriginalAlgo>>,
+    removed_seams_horizontal: Arc<Mutex<Vec<Vec<u32>>>>,
+    energy_backend: EnergyBackend,
+    gpu_energy: Option<GpuEnergyPass>,
+    // How many more `step_gpu_precompute` calls the GPU-backend vertical
+    // carve needs, and when it started (for the "entire carve took"
+    // logging); zero/`None` once done, or always for the CPU backend (which
+    // precomputes on its own background thread instead).
+    gpu_precompute_remaining: u32,
+    gpu_precompute_start: Option<Instant>,
 }
 
 impl CarvingEngine {
-    fn new(image: DynamicImage, gl: &glow::Context) -> Self {
+    fn new(
+        image: DynamicImage,
+        gl: &glow::Context,
+        energy_backend: EnergyBackend,
+        energy_mode: EnergyMode,
+    ) -> Self {
         let pixels_rgb8: Vec<[u8; 3]> = image
             .to_rgb8()
             .pixels()
             .map(|x| [x[0], x[1], x[2]])
             .collect();
 
-        let algo = Arc::new(Mutex::new(OriginalAlgo::new(
-            pixels_rgb8,
+        let mut algo = OriginalAlgo::new_with_backend(
+            pixels_rgb8.clone(),
             image.width(),
             image.height(),
-        )));
+            energy_backend,
+        );
+        algo.set_energy_mode(energy_mode);
+        let algo = Arc::new(Mutex::new(algo));
 
         let removed_seams = Arc::new(Mutex::new(Vec::new()));
 
+        // Precomputed independently of `algo`, since it shrinks height
+        // instead of width: the vertical and horizontal seam orders are
+        // each only valid against the original, un-carved pixel grid. Always
+        // CPU energy, since the GPU energy pass is sized for the unrotated
+        // image and the carve runs on a background thread without a GL
+        // context.
+        let mut algo_horizontal = OriginalAlgo::new_with_backend(
+            pixels_rgb8,
+            image.width(),
+            image.height(),
+            EnergyBackend::Cpu,
+        );
+        algo_horizontal.set_energy_mode(energy_mode);
+        let algo_horizontal = Arc::new(Mutex::new(algo_horizontal));
+
+        let removed_seams_horizontal = Arc::new(Mutex::new(Vec::new()));
+
         let canvas = Arc::new(Mutex::new(GlowImageCanvas::new(
             gl,
             image.width(),
@@ -69,42 +254,83 @@ impl CarvingEngine {
             image.color().has_alpha(),
         )));
 
+        let gpu_energy = match energy_backend {
+            EnergyBackend::Gpu => Some(GpuEnergyPass::new(gl, image.width(), image.height())),
+            EnergyBackend::Cpu => None,
+        };
+
         let mut ret = Self {
             canvas,
             image,
             algo,
             removed_seams,
+            algo_horizontal,
+            removed_seams_horizontal,
+            energy_backend,
+            gpu_energy,
+            gpu_precompute_remaining: 0,
+            gpu_precompute_start: None,
         };
 
-        ret.run_engine();
+        ret.run_engine(gl);
         ret
     }
 
+    // Re-derive `algo`/`algo_horizontal` from the original image under a new
+    // energy mode and re-run the precompute, so flipping the Backward/Forward
+    // radio takes effect immediately instead of only on the next "Open
+    // image". Mirrors `new`'s setup, minus the canvas/GPU-energy-pass
+    // allocation (those don't depend on the energy mode).
+    fn set_energy_mode(&mut self, gl: &glow::Context, energy_mode: EnergyMode) {
+        let pixels_rgb8: Vec<[u8; 3]> = self
+            .image
+            .to_rgb8()
+            .pixels()
+            .map(|x| [x[0], x[1], x[2]])
+            .collect();
+
+        let mut algo = OriginalAlgo::new_with_backend(
+            pixels_rgb8.clone(),
+            self.image.width(),
+            self.image.height(),
+            self.energy_backend,
+        );
+        algo.set_energy_mode(energy_mode);
+        self.algo = Arc::new(Mutex::new(algo));
+        self.removed_seams = Arc::new(Mutex::new(Vec::new()));
+
+        let mut algo_horizontal = OriginalAlgo::new_with_backend(
+            pixels_rgb8.clone(),
+            self.image.width(),
+            self.image.height(),
+            EnergyBackend::Cpu,
+        );
+        algo_horizontal.set_energy_mode(energy_mode);
+        self.algo_horizontal = Arc::new(Mutex::new(algo_horizontal));
+        self.removed_seams_horizontal = Arc::new(Mutex::new(Vec::new()));
+
+        let flat: Vec<u8> = pixels_rgb8.into_iter().flatten().collect();
+        self.canvas.lock().unwrap().update_pixels(
+            gl,
+            self.image.width(),
+            self.image.height(),
+            &flat,
+            false,
+        );
+
+        self.run_engine(gl);
+    }
+
     fn remove_seams(&mut self, gl: &glow::Context, num_seams: u32) {
         let start = Instant::now();
-        let mut pixel_data: Vec<[u8; 3]> = self
+        let pixel_data: Vec<[u8; 3]> = self
             .image
             .to_rgb8()
             .pixels()
             .map(|x| [x[0], x[1], x[2]])
             .collect();
-        for i in 0..num_seams {
-            let mut k = 0;
-            let to_remove = &self.removed_seams.lock().unwrap()[i as usize];
-            pixel_data = pixel_data
-                .iter()
-                .enumerate()
-                .filter(|(i, _pix)| {
-                    if k != to_remove.len() && *i == to_remove[k] as usize {
-                        k += 1;
-                        false
-                    } else {
-                        true
-                    }
-                })
-                .map(|(_i, pix)| *pix)
-                .collect();
-        }
+        let pixel_data =
+            narrow_with_seams(pixel_data, &self.removed_seams.lock().unwrap(), num_seams);
         let flat: Vec<u8> = pixel_data.into_iter().flatten().collect();
         self.canvas.lock().unwrap().update_pixels(
             gl,
@@ -116,39 +342,252 @@ impl CarvingEngine {
         println!("removing {} seams took {:?}", num_seams, start.elapsed());
     }
 
-    fn run_engine(&mut self) {
+    fn remove_seams_horizontal(&mut self, gl: &glow::Context, num_seams: u32) {
+        let start = Instant::now();
+        let (width, height) = (self.image.width(), self.image.height());
+        let pixel_data: Vec<[u8; 3]> = self
+            .image
+            .to_rgb8()
+            .pixels()
+            .map(|x| [x[0], x[1], x[2]])
+            .collect();
+        let transposed = transpose_pixels(&pixel_data, width, height);
+        let transposed = narrow_with_seams(
+            transposed,
+            &self.removed_seams_horizontal.lock().unwrap(),
+            num_seams,
+        );
+        let new_height = height - num_seams;
+        let pixel_data = transpose_pixels(&transposed, new_height, width);
+        let flat: Vec<u8> = pixel_data.into_iter().flatten().collect();
+        self.canvas
+            .lock()
+            .unwrap()
+            .update_pixels(gl, width, new_height, &flat, false);
+        println!(
+            "removing {} horizontal seams took {:?}",
+            num_seams,
+            start.elapsed()
+        );
+    }
+
+    // Grow the carved-away dimension back by duplicating the k lowest-energy
+    // precomputed seams (the earliest-removed ones) instead of deleting them.
+    fn insert_seams(&mut self, gl: &glow::Context, num_seams: u32) {
+        let start = Instant::now();
+        let pixel_data: Vec<[u8; 3]> = self
+            .image
+            .to_rgb8()
+            .pixels()
+            .map(|x| [x[0], x[1], x[2]])
+            .collect();
+        let seams = self.removed_seams.lock().unwrap().clone();
+        let applied = num_seams.min(seams.len() as u32);
+        let pixel_data = widen_with_seams(pixel_data, &seams, applied, self.image.width());
+        let flat: Vec<u8> = pixel_data.into_iter().flatten().collect();
+        self.canvas.lock().unwrap().update_pixels(
+            gl,
+            self.image.width() + applied,
+            self.image.height(),
+            &flat,
+            false,
+        );
+        println!("inserting {} seams took {:?}", applied, start.elapsed());
+    }
+
+    fn insert_seams_horizontal(&mut self, gl: &glow::Context, num_seams: u32) {
+        let start = Instant::now();
+        let (width, height) = (self.image.width(), self.image.height());
+        let pixel_data: Vec<[u8; 3]> = self
+            .image
+            .to_rgb8()
+            .pixels()
+            .map(|x| [x[0], x[1], x[2]])
+            .collect();
+        let transposed = transpose_pixels(&pixel_data, width, height);
+        let seams = self.removed_seams_horizontal.lock().unwrap().clone();
+        let applied = num_seams.min(seams.len() as u32);
+        let transposed = widen_with_seams(transposed, &seams, applied, height);
+        let new_height = height + applied;
+        let pixel_data = transpose_pixels(&transposed, new_height, width);
+        let flat: Vec<u8> = pixel_data.into_iter().flatten().collect();
+        self.canvas
+            .lock()
+            .unwrap()
+            .update_pixels(gl, width, new_height, &flat, false);
+        println!(
+            "inserting {} horizontal seams took {:?}",
+            applied,
+            start.elapsed()
+        );
+    }
+
+    fn run_engine(&mut self, gl: &glow::Context) {
         let width = self.image.width().clone();
-        let algo = self.algo.clone();
-        let removed_seams = self.removed_seams.clone();
+
+        match self.energy_backend {
+            EnergyBackend::Cpu => {
+                let algo = self.algo.clone();
+                let removed_seams = self.removed_seams.clone();
+                thread::spawn(move || {
+                    let process_start = Instant::now();
+                    let mut algo = algo.lock().unwrap();
+                    for _carve_iteration in 0..width - 10 {
+                        let removed_incices = algo.remove_vertical_seam();
+                        removed_seams.lock().unwrap().push(removed_incices);
+                    }
+                    println!("entire carve took {:?}", process_start.elapsed());
+                });
+            }
+            EnergyBackend::Gpu => {
+                // glow::Context is tied to the thread that owns the GL
+                // context, so this can't be backgrounded like the CPU path;
+                // instead of looping to completion here (which would freeze
+                // the window for the whole carve), just arm the counter and
+                // let `step_gpu_precompute` advance it one iteration per
+                // `update()` call.
+                self.gpu_precompute_remaining = width - 10;
+                self.gpu_precompute_start = Some(Instant::now());
+            }
+        }
+
+        let height = self.image.height().clone();
+        let algo_horizontal = self.algo_horizontal.clone();
+        let removed_seams_horizontal = self.removed_seams_horizontal.clone();
         thread::spawn(move || {
             let process_start = Instant::now();
-            let mut algo = algo.lock().unwrap();
-            for _carve_iteration in 0..width - 10 {
-                let removed_incices = algo.remove_vertical_seam();
-                removed_seams.lock().unwrap().push(removed_incices);
+            let mut algo_horizontal = algo_horizontal.lock().unwrap();
+            for _carve_iteration in 0..height - 10 {
+                let removed_incices = algo_horizontal.remove_horizontal_seam();
+                removed_seams_horizontal
+                    .lock()
+                    .unwrap()
+                    .push(removed_incices);
             }
-            println!("entire carve took {:?}", process_start.elapsed());
+            println!("entire horizontal carve took {:?}", process_start.elapsed());
         });
     }
 
-    // draw the image data to the canvas
-    fn draw(&self, ui: &mut egui::Ui, seams_removed: u32) {
+    fn load_shader_preset(
+        &mut self,
+        gl: &glow::Context,
+        configs: Vec<post_process::ShaderPassConfig>,
+    ) -> Result<(), String> {
+        self.canvas.lock().unwrap().load_post_process(gl, configs)
+    }
+
+    fn gpu_precompute_in_progress(&self) -> bool {
+        self.gpu_precompute_remaining > 0
+    }
+
+    // Run one iteration of the GPU-backend vertical carve. Called once per
+    // `update()` frame instead of looping to completion in `run_engine`, so
+    // the window keeps repainting (and stays responsive) while a carve is
+    // underway.
+    fn step_gpu_precompute(&mut self, gl: &glow::Context) {
+        if self.gpu_precompute_remaining == 0 {
+            return;
+        }
+
+        let mut algo = self.algo.lock().unwrap();
+        let gpu_energy = self
+            .gpu_energy
+            .as_ref()
+            .expect("Gpu backend requires a GpuEnergyPass");
+
+        // Re-upload the canvas texture from the algo's current
+        // (already-shrunk) pixel grid before every energy pass, sized to its
+        // current width/height: the energy pass has to see this iteration's
+        // carved image, not the original one `GlowImageCanvas::new` uploaded.
+        let (carved_width, carved_height) = (algo.width(), algo.height());
+        let flat: Vec<u8> = algo.pixels().iter().flatten().copied().collect();
+        let texture = {
+            let mut canvas = self.canvas.lock().unwrap();
+            canvas.update_pixels(gl, carved_width, carved_height, &flat, false);
+            canvas.texture()
+        };
+        let energy_matrix = gpu_energy.compute(gl, texture, carved_width, carved_height);
+        algo.set_gpu_energy(energy_matrix);
+        let removed_incices = algo.remove_vertical_seam();
+        self.removed_seams.lock().unwrap().push(removed_incices);
+        drop(algo);
+
+        self.gpu_precompute_remaining -= 1;
+        if self.gpu_precompute_remaining == 0 {
+            // The per-call re-uploads above left the canvas showing the
+            // fully-carved preview image; restore the original so the slider still starts
+            // at 0 seams removed, matching the CPU backend (which never
+            // touches the canvas during precompute).
+            let pixels_rgb8: Vec<[u8; 3]> = self
+                .image
+                .to_rgb8()
+                .pixels()
+                .map(|x| [x[0], x[1], x[2]])
+                .collect();
+            let flat: Vec<u8> = pixels_rgb8.into_iter().flatten().collect();
+            self.canvas.lock().unwrap().update_pixels(
+                gl,
+                self.image.width(),
+                self.image.height(),
+                &flat,
+                false,
+            );
+            if let Some(start) = self.gpu_precompute_start.take() {
+                println!("entire carve took {:?}", start.elapsed());
+            }
+        }
+    }
+
+    // draw the image data to the canvas, optionally overlaying the first
+    // `num_vertical`/`num_horizontal` precomputed seams as colored lines
+    fn draw(
+        &self,
+        ui: &mut egui::Ui,
+        width_delta: i32,
+        height_delta: i32,
+        show_seams: bool,
+        num_vertical: u32,
+        num_horizontal: u32,
+    ) {
         let (rect, _) = ui.allocate_exact_size(
             // can scale width and height down if image is too big
             egui::Vec2::new(
-                (self.image.width() - seams_removed) as f32,
-                self.image.height() as f32,
+                (self.image.width() as i32 + width_delta) as f32,
+                (self.image.height() as i32 + height_delta) as f32,
             ),
             egui::Sense::drag(),
         );
 
+        let overlay = show_seams.then(|| {
+            let (width, height) = (self.image.width(), self.image.height());
+            let vertical = seam_overlay_points(
+                &self.removed_seams.lock().unwrap(),
+                num_vertical,
+                width,
+                height,
+                false,
+            );
+            let horizontal = seam_overlay_points(
+                &self.removed_seams_horizontal.lock().unwrap(),
+                num_horizontal,
+                width,
+                height,
+                true,
+            );
+            (vertical, horizontal)
+        });
+
         let canvas = self.canvas.clone();
         let callback = egui::PaintCallback {
             callback: Arc::new(CallbackFn::new(move |_info, painter| {
-                canvas
-                    .lock()
-                    .expect("Failed to grab lock")
-                    .paint(painter.gl());
+                let mut canvas = canvas.lock().expect("Failed to grab lock");
+                match &overlay {
+                    Some((vertical, horizontal)) => {
+                        canvas.set_seam_overlay(painter.gl(), vertical, horizontal)
+                    }
+                    None => canvas.clear_seam_overlay(),
+                }
+                canvas.paint(painter.gl());
             })),
             rect,
         };
@@ -156,12 +595,31 @@ impl CarvingEngine {
     }
 }
 
+// Whether the width/height sliders remove precomputed seams (shrink) or
+// re-insert them by duplication (grow).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ResizeMode {
+    Shrink,
+    Grow,
+}
+
+impl Default for ResizeMode {
+    fn default() -> Self {
+        Self::Shrink
+    }
+}
+
 // Contains main app state
 #[derive(Default)]
 struct LiquidResizeApp {
     image_bundle: Option<CarvingEngine>,
     status: LoadStatus,
     slider_value: u32,
+    slider_value_horizontal: u32,
+    resize_mode: ResizeMode,
+    energy_mode: EnergyMode,
+    energy_backend: EnergyBackend,
+    show_seam_overlay: bool,
 }
 
 impl LiquidResizeApp {
@@ -178,11 +636,35 @@ impl eframe::App for LiquidResizeApp {
         let gl = frame.gl().expect("eframe not running with glow backend");
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Liquid Resize (Seam Carving) Demonstration");
+
+            let energy_mode_before = self.energy_mode;
+            ui.horizontal(|ui| {
+                ui.label("Energy criterion:");
+                ui.radio_value(&mut self.energy_mode, EnergyMode::Backward, "Backward");
+                ui.radio_value(&mut self.energy_mode, EnergyMode::Forward, "Forward");
+            });
+            if self.energy_mode != energy_mode_before {
+                if let Some(image_bundle) = &mut self.image_bundle {
+                    image_bundle.set_energy_mode(gl, self.energy_mode);
+                }
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Energy backend:");
+                ui.radio_value(&mut self.energy_backend, EnergyBackend::Cpu, "CPU");
+                ui.radio_value(&mut self.energy_backend, EnergyBackend::Gpu, "GPU");
+            });
+
             if ui.button("Open image").clicked() {
                 if let Some(path) = FileDialog::new().pick_file() {
                     match image::open(&path) {
                         Ok(image) => {
-                            self.image_bundle = Some(CarvingEngine::new(image.flipv(), gl));
+                            self.image_bundle = Some(CarvingEngine::new(
+                                image.flipv(),
+                                gl,
+                                self.energy_backend,
+                                self.energy_mode,
+                            ));
                             let loaded_status = format!("{} loaded!", path.display().to_string());
                             self.status = LoadStatus::Loaded(loaded_status);
                         }
@@ -208,23 +690,104 @@ impl eframe::App for LiquidResizeApp {
             });
 
             if let Some(image_bundle) = &mut self.image_bundle {
+                if image_bundle.gpu_precompute_in_progress() {
+                    image_bundle.step_gpu_precompute(gl);
+                    // Precompute only advances while frames keep arriving;
+                    // without this, a paused/idle UI would stall it forever.
+                    ctx.request_repaint();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Resize mode:");
+                    ui.radio_value(&mut self.resize_mode, ResizeMode::Shrink, "Shrink (carve)");
+                    ui.radio_value(&mut self.resize_mode, ResizeMode::Grow, "Grow (enlarge)");
+                });
+
+                ui.checkbox(
+                    &mut self.show_seam_overlay,
+                    "Show seam overlay (vertical red, horizontal blue)",
+                );
+
+                let seams_removed = image_bundle.removed_seams.lock().unwrap().len();
+                let seams_removed_horizontal =
+                    image_bundle.removed_seams_horizontal.lock().unwrap().len();
+
+                let (width_delta, height_delta) = match self.resize_mode {
+                    ResizeMode::Shrink => (
+                        -(self.slider_value as i32),
+                        -(self.slider_value_horizontal as i32),
+                    ),
+                    ResizeMode::Grow => (
+                        self.slider_value.min(seams_removed as u32) as i32,
+                        self.slider_value_horizontal
+                            .min(seams_removed_horizontal as u32) as i32,
+                    ),
+                };
+
                 egui::Frame::canvas(ui.style()).show(ui, |ui| {
-                    image_bundle.draw(ui, self.slider_value);
+                    image_bundle.draw(
+                        ui,
+                        width_delta,
+                        height_delta,
+                        self.show_seam_overlay,
+                        self.slider_value,
+                        self.slider_value_horizontal,
+                    );
                 });
                 ui.label("image is loaded!");
 
-                let seams_removed = image_bundle.removed_seams.lock().unwrap().len();
+                if ui.button("Load shader preset").clicked() {
+                    if let Some(path) = FileDialog::new().pick_file() {
+                        match std::fs::read_to_string(&path)
+                            .map_err(|err| err.to_string())
+                            .and_then(|contents| post_process::parse_preset(&contents))
+                        {
+                            Ok(configs) => {
+                                if let Err(err) = image_bundle.load_shader_preset(gl, configs) {
+                                    self.status = LoadStatus::Error(err);
+                                }
+                            }
+                            Err(err) => self.status = LoadStatus::Error(err),
+                        }
+                    }
+                }
+
                 ui.label(format!(
-                    "carving progress: {}%",
+                    "vertical carving progress: {}%",
                     ((seams_removed * 100) as f32 / (image_bundle.image.width() - 10) as f32)
                         as u32
                 ));
+                ui.label(format!(
+                    "horizontal carving progress: {}%",
+                    ((seams_removed_horizontal * 100) as f32
+                        / (image_bundle.image.height() - 10) as f32) as u32
+                ));
 
                 let slider = egui::Slider::new(&mut self.slider_value, 0..=seams_removed as u32)
-                    .text("slide to preview interpolation (normal resize), release to carve")
-                    .show_value(false); // turn to false?
+                    .text("width seams (slide to preview, release to apply)")
+                    .show_value(false);
                 if ui.add(slider).drag_released() {
-                    image_bundle.remove_seams(gl, self.slider_value);
+                    match self.resize_mode {
+                        ResizeMode::Shrink => image_bundle.remove_seams(gl, self.slider_value),
+                        ResizeMode::Grow => image_bundle.insert_seams(gl, self.slider_value),
+                    }
+                };
+
+                let slider_horizontal = egui::Slider::new(
+                    &mut self.slider_value_horizontal,
+                    0..=seams_removed_horizontal as u32,
+                )
+                .text("height seams (slide to preview, release to apply)")
+                .show_value(false);
+                if ui.add(slider_horizontal).drag_released() {
+                    match self.resize_mode {
+                        ResizeMode::Shrink => {
+                            image_bundle.remove_seams_horizontal(gl, self.slider_value_horizontal)
+                        }
+                        ResizeMode::Grow => {
+                            image_bundle.insert_seams_horizontal(gl, self.slider_value_horizontal)
+                        }
+                    }
                 };
             }
         });
@@ -236,8 +799,34 @@ struct GlowImageCanvas {
     program: glow::Program,
     tex: NativeTexture,
     pbo: NativeBuffer,
+    width: u32,
+    height: u32,
+    shader_registry: ShaderRegistry,
+    post_process: ShaderChain,
+    line_program: NativeProgram,
+    line_vbo: NativeBuffer,
+    line_pos_attrib: u32,
+    overlay_ranges: Vec<(i32, i32, [f32; 4])>,
 }
 
+const VERTICAL_SEAM_COLOR: [f32; 4] = [1.0, 0.1, 0.1, 1.0];
+const HORIZONTAL_SEAM_COLOR: [f32; 4] = [0.1, 0.4, 1.0, 1.0];
+
+const LINE_VERTEX: &str = r#"
+    in vec2 pos;
+    void main() {
+        gl_Position = vec4(pos, 0.0, 1.0);
+    }
+"#;
+const LINE_FRAGMENT: &str = r#"
+    precision mediump float;
+    out vec4 vFragColor;
+    uniform vec4 lineColor;
+    void main() {
+        vFragColor = lineColor;
+    }
+"#;
+
 impl GlowImageCanvas {
     fn new(
         gl: &glow::Context,
@@ -247,71 +836,31 @@ impl GlowImageCanvas {
         has_alpha: bool,
     ) -> Self {
         use glow::HasContext as _;
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
-        unsafe {
-            let program = gl.create_program().expect("Failed to create program");
-            let (vertex_shader_source, fragment_shader_source) = (
-                r#"
-                    const vec2 verts[6] = vec2[6](
-                        vec2(-1.0, -1.0),
-                        vec2(1.0, -1.0),
-                        vec2(1.0, 1.0),
-                        vec2(-1.0, 1.0),
-                        vec2(-1.0, -1.0),
-                        vec2(1.0, 1.0)
-                    );
-                    out vec2 vUV;
-                    void main() {
-                        gl_Position = vec4(verts[gl_VertexID], 0.0, 1.0);
-                        vUV = (verts[gl_VertexID] + 1) / 2;
-                    }
-                "#,
-                r#"
-                    precision mediump float;
-                    in vec2 vUV;
-                    out vec4 vFragColor;
-                    uniform sampler2D textureMap;
-                    void main() {
-                        vFragColor = texture(textureMap, vUV);
-                    }
-                "#,
-            );
-
-            let shader_sources = [
-                (glow::VERTEX_SHADER, vertex_shader_source),
-                (glow::FRAGMENT_SHADER, fragment_shader_source),
-            ];
-            let shaders: Vec<NativeShader> = shader_sources
-                .iter()
-                .map(|(shader_type, shader_source)| {
-                    let shader = gl
-                        .create_shader(*shader_type)
-                        .expect("Cannot create shader");
-                    gl.shader_source(shader, &format!("{}\n{}", shader_version, shader_source));
-                    gl.compile_shader(shader);
-                    assert!(
-                        gl.get_shader_compile_status(shader),
-                        "Failed to compile {shader_type}: {}",
-                        gl.get_shader_info_log(shader)
-                    );
-                    gl.attach_shader(program, shader);
-                    shader
-                })
-                .collect();
-
-            gl.link_program(program);
-            if !gl.get_program_link_status(program) {
-                panic!("{}", gl.get_program_info_log(program));
+        const PASSTHROUGH_FRAGMENT: &str = r#"
+            precision mediump float;
+            in vec2 vUV;
+            out vec4 vFragColor;
+            uniform sampler2D textureMap;
+            void main() {
+                vFragColor = texture(textureMap, vUV);
             }
+        "#;
 
-            for shader in shaders {
-                gl.detach_shader(program, shader);
-                gl.delete_shader(shader);
-            }
+        let mut shader_registry = ShaderRegistry::builtin();
+        let program = shader_registry
+            .compile(
+                gl,
+                shader_registry::FULLSCREEN_QUAD_VERTEX_MAIN,
+                PASSTHROUGH_FRAGMENT,
+            )
+            .expect("built-in passthrough shader failed to compile");
+        let line_program = shader_registry
+            .compile(gl, LINE_VERTEX, LINE_FRAGMENT)
+            .expect("built-in line shader failed to compile");
+        let line_pos_attrib = unsafe { gl.get_attrib_location(line_program, "pos") }
+            .expect("line shader is missing the pos attribute");
+        unsafe {
+            let line_vbo = gl.create_buffer().expect("Failed to create line VBO");
 
             // texture setup
             let tex = gl.create_texture().expect("Failed to create texture");
@@ -327,6 +876,22 @@ impl GlowImageCanvas {
                 glow::TEXTURE_MAG_FILTER,
                 glow::LINEAR as i32,
             );
+            // Clamp rather than GL's default repeat: the GPU energy pass
+            // samples this texture's four texel neighbours (`dualGradientEnergy`
+            // in shader_registry.rs), and at the border columns/rows that would
+            // otherwise wrap around and read the opposite edge instead of
+            // duplicating the edge pixel like `calculate_energy_matrix` does
+            // on the CPU.
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_S,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_T,
+                glow::CLAMP_TO_EDGE as i32,
+            );
 
             let pbo = gl.create_buffer().expect("Failed to create PBO");
             gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, Some(pbo));
@@ -345,22 +910,132 @@ impl GlowImageCanvas {
                 None,
             );
 
-            Self { program, tex, pbo }
+            Self {
+                program,
+                tex,
+                pbo,
+                width,
+                height,
+                shader_registry,
+                post_process: ShaderChain::default(),
+                line_program,
+                line_vbo,
+                line_pos_attrib,
+                overlay_ranges: Vec::new(),
+            }
+        }
+    }
+
+    // The currently bound image texture, e.g. for an energy pass to sample.
+    fn texture(&self) -> NativeTexture {
+        self.tex
+    }
+
+    // Load a post-processing pass chain, replacing whatever was loaded
+    // before. An empty `configs` falls back to the plain passthrough draw.
+    fn load_post_process(
+        &mut self,
+        gl: &glow::Context,
+        configs: Vec<post_process::ShaderPassConfig>,
+    ) -> Result<(), String> {
+        self.post_process.load(
+            gl,
+            &mut self.shader_registry,
+            configs,
+            self.width,
+            self.height,
+        )
+    }
+
+    // Upload the seam-overlay line points as normalized device coordinates:
+    // `vertical`/`horizontal` are each a flat point buffer plus the
+    // (first, count) range of each seam within it, as produced by
+    // `seam_overlay_points`.
+    fn set_seam_overlay(
+        &mut self,
+        gl: &glow::Context,
+        vertical: &(Vec<[f32; 2]>, Vec<(i32, i32)>),
+        horizontal: &(Vec<[f32; 2]>, Vec<(i32, i32)>),
+    ) {
+        use glow::HasContext as _;
+        let mut points = vertical.0.clone();
+        let vertical_len = points.len() as i32;
+        points.extend_from_slice(&horizontal.0);
+
+        self.overlay_ranges = vertical
+            .1
+            .iter()
+            .map(|&(first, count)| (first, count, VERTICAL_SEAM_COLOR))
+            .chain(
+                horizontal
+                    .1
+                    .iter()
+                    .map(|&(first, count)| (first + vertical_len, count, HORIZONTAL_SEAM_COLOR)),
+            )
+            .collect();
+
+        let bytes: Vec<u8> = points
+            .iter()
+            .flatten()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.line_vbo));
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &bytes, glow::STREAM_DRAW);
         }
     }
 
-    // Draw the texture previously loaded on the GPU via new() or update_pixels()
-    fn paint(&self, gl: &glow::Context) {
+    fn clear_seam_overlay(&mut self) {
+        self.overlay_ranges.clear();
+    }
+
+    // Draw each seam range as its own disconnected line strip, on top of
+    // whatever `paint` already rendered to the currently bound framebuffer.
+    fn draw_seam_overlay(&self, gl: &glow::Context) {
         use glow::HasContext as _;
+        if self.overlay_ranges.is_empty() {
+            return;
+        }
         unsafe {
-            gl.use_program(Some(self.program));
-            gl.bind_texture(glow::TEXTURE_2D, Some(self.tex));
-            gl.uniform_1_i32(
-                gl.get_uniform_location(self.program, "textureMap").as_ref(),
-                0,
-            );
-            gl.draw_arrays(glow::TRIANGLES, 0, 6);
+            gl.use_program(Some(self.line_program));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.line_vbo));
+            gl.enable_vertex_attrib_array(self.line_pos_attrib);
+            gl.vertex_attrib_pointer_f32(self.line_pos_attrib, 2, glow::FLOAT, false, 0, 0);
+            gl.line_width(2.0);
+            for &(first, count, color) in &self.overlay_ranges {
+                gl.uniform_4_f32(
+                    gl.get_uniform_location(self.line_program, "lineColor")
+                        .as_ref(),
+                    color[0],
+                    color[1],
+                    color[2],
+                    color[3],
+                );
+                gl.draw_arrays(glow::LINE_STRIP, first, count);
+            }
+            gl.disable_vertex_attrib_array(self.line_pos_attrib);
+        }
+    }
+
+    // Draw the texture previously loaded on the GPU via new() or update_pixels(),
+    // running it through the post-process chain if one is loaded, then the
+    // seam overlay (if any) on top.
+    fn paint(&mut self, gl: &glow::Context) {
+        use glow::HasContext as _;
+        if self.post_process.is_empty() {
+            unsafe {
+                gl.use_program(Some(self.program));
+                gl.bind_texture(glow::TEXTURE_2D, Some(self.tex));
+                gl.uniform_1_i32(
+                    gl.get_uniform_location(self.program, "textureMap").as_ref(),
+                    0,
+                );
+                gl.draw_arrays(glow::TRIANGLES, 0, 6);
+            }
+        } else {
+            self.post_process.run(gl, self.tex, self.width, self.height);
         }
+        self.draw_seam_overlay(gl);
     }
 
     // Change the pixel data on the texture via Pixelbuffer
@@ -391,5 +1066,7 @@ impl GlowImageCanvas {
                 None,
             );
         }
+        self.width = width;
+        self.height = height;
     }
 }
@@ -0,0 +1,273 @@
+// A configurable chain of post-processing shader passes applied to the
+// carved image before it hits the screen: sharpening, edge highlighting,
+// CRT-style effects, etc., loaded live from a preset file instead of being
+// compiled into the binary.
+use glow::{HasContext as _, NativeFramebuffer, NativeProgram, NativeTexture};
+
+use crate::shader_registry::ShaderRegistry;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FilterMode {
+    Nearest,
+    Linear,
+}
+
+impl FilterMode {
+    fn gl_const(self) -> u32 {
+        match self {
+            FilterMode::Nearest => glow::NEAREST,
+            FilterMode::Linear => glow::LINEAR,
+        }
+    }
+}
+
+/// One pass loaded from a preset file: a vertex+fragment shader pair, how
+/// large its output should be relative to the carved image, and how that
+/// output is filtered when the next pass samples it.
+pub struct ShaderPassConfig {
+    pub vertex_source: String,
+    pub fragment_source: String,
+    pub scale: f32,
+    pub filter: FilterMode,
+}
+
+/// Parse a pass-chain preset file. Each pass is a block ending in
+/// `===END===`; its first line is a `scale=<f32> filter=<nearest|linear>`
+/// header, followed by the vertex shader source, a `===FRAGMENT===`
+/// marker, then the fragment shader source.
+pub fn parse_preset(contents: &str) -> Result<Vec<ShaderPassConfig>, String> {
+    let mut configs = Vec::new();
+    for block in contents.split("===END===") {
+        let block = block.trim();
+        if block.is_empty() {
+            continue;
+        }
+
+        let (header, rest) = block
+            .split_once('\n')
+            .ok_or("pass block is missing a header line")?;
+
+        let mut scale = 1.0f32;
+        let mut filter = FilterMode::Linear;
+        for field in header.split_whitespace() {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| format!("malformed header field: {field}"))?;
+            match key {
+                "scale" => {
+                    scale = value
+                        .parse()
+                        .map_err(|_| format!("invalid scale: {value}"))?
+                }
+                "filter" => {
+                    filter = match value {
+                        "nearest" => FilterMode::Nearest,
+                        "linear" => FilterMode::Linear,
+                        _ => return Err(format!("unknown filter mode: {value}")),
+                    }
+                }
+                _ => return Err(format!("unknown header field: {key}")),
+            }
+        }
+
+        let (vertex_source, fragment_source) = rest
+            .split_once("===FRAGMENT===")
+            .ok_or("pass block is missing a ===FRAGMENT=== marker")?;
+
+        configs.push(ShaderPassConfig {
+            vertex_source: vertex_source.trim().to_string(),
+            fragment_source: fragment_source.trim().to_string(),
+            scale,
+            filter,
+        });
+    }
+
+    if configs.is_empty() {
+        return Err("preset file contained no passes".to_string());
+    }
+    Ok(configs)
+}
+
+// A compiled `ShaderPassConfig` plus the framebuffer/texture it renders
+// into. The final pass in a chain never reads its own target; it renders
+// straight to screen instead.
+struct ShaderPass {
+    program: NativeProgram,
+    fbo: NativeFramebuffer,
+    tex: NativeTexture,
+    filter: FilterMode,
+    scale: f32,
+    width: u32,
+    height: u32,
+}
+
+impl ShaderPass {
+    fn new(
+        gl: &glow::Context,
+        registry: &mut ShaderRegistry,
+        config: ShaderPassConfig,
+        base_width: u32,
+        base_height: u32,
+    ) -> Result<Self, String> {
+        let program = registry.compile(gl, &config.vertex_source, &config.fragment_source)?;
+        unsafe {
+            let tex = gl.create_texture().expect("Failed to create texture");
+            let fbo = gl.create_framebuffer().expect("Failed to create FBO");
+            let mut pass = Self {
+                program,
+                fbo,
+                tex,
+                filter: config.filter,
+                scale: config.scale,
+                width: 0,
+                height: 0,
+            };
+            pass.resize(gl, base_width, base_height);
+            Ok(pass)
+        }
+    }
+
+    // Free this pass's own GL objects. The program isn't touched here: it's
+    // owned by the `ShaderRegistry` cache, keyed by source, and may still be
+    // referenced by a future reload of the same preset.
+    fn delete(self, gl: &glow::Context) {
+        unsafe {
+            gl.delete_texture(self.tex);
+            gl.delete_framebuffer(self.fbo);
+        }
+    }
+
+    // (Re)allocate this pass's target at `scale * base` size, skipping the
+    // work if the carved image's size hasn't actually changed.
+    fn resize(&mut self, gl: &glow::Context, base_width: u32, base_height: u32) {
+        let width = ((base_width as f32 * self.scale).round() as u32).max(1);
+        let height = ((base_height as f32 * self.scale).round() as u32).max(1);
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.tex));
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MIN_FILTER,
+                self.filter.gl_const() as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MAG_FILTER,
+                self.filter.gl_const() as i32,
+            );
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA as i32,
+                width as i32,
+                height as i32,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                None,
+            );
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(self.tex),
+                0,
+            );
+            assert_eq!(
+                gl.check_framebuffer_status(glow::FRAMEBUFFER),
+                glow::FRAMEBUFFER_COMPLETE,
+                "Post-process pass framebuffer is incomplete"
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        self.width = width;
+        self.height = height;
+    }
+}
+
+/// A live-reloadable chain of post-processing passes. Empty by default, in
+/// which case `GlowImageCanvas` falls back to its plain passthrough draw.
+#[derive(Default)]
+pub struct ShaderChain {
+    passes: Vec<ShaderPass>,
+}
+
+impl ShaderChain {
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Compile and swap in a new pass chain, or leave the current chain
+    /// untouched and report why on the first pass that fails to compile —
+    /// this is reachable from a user-picked preset file, so a typo'd
+    /// `#include` or a GLSL syntax error should surface as a status message
+    /// rather than aborting the process.
+    pub fn load(
+        &mut self,
+        gl: &glow::Context,
+        registry: &mut ShaderRegistry,
+        configs: Vec<ShaderPassConfig>,
+        base_width: u32,
+        base_height: u32,
+    ) -> Result<(), String> {
+        let mut new_passes = Vec::with_capacity(configs.len());
+        for config in configs {
+            match ShaderPass::new(gl, registry, config, base_width, base_height) {
+                Ok(pass) => new_passes.push(pass),
+                Err(err) => {
+                    for pass in new_passes {
+                        pass.delete(gl);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        for pass in std::mem::replace(&mut self.passes, new_passes) {
+            pass.delete(gl);
+        }
+        Ok(())
+    }
+
+    // Render the chain against `source_tex` (the carved image). Pass 0
+    // reads `source_tex` as both "sourceTex" and "prevTex"; later passes
+    // keep "sourceTex" pointing at the original carved image so they can
+    // still blend with the unprocessed result, while "prevTex" is the
+    // immediately preceding pass's output. The last pass renders to screen.
+    pub fn run(&mut self, gl: &glow::Context, source_tex: NativeTexture, width: u32, height: u32) {
+        let Some(last) = self.passes.len().checked_sub(1) else {
+            return;
+        };
+        let mut prev = source_tex;
+        unsafe {
+            for (i, pass) in self.passes.iter_mut().enumerate() {
+                pass.resize(gl, width, height);
+                if i == last {
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                    gl.viewport(0, 0, width as i32, height as i32);
+                } else {
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, Some(pass.fbo));
+                    gl.viewport(0, 0, pass.width as i32, pass.height as i32);
+                }
+
+                gl.use_program(Some(pass.program));
+                gl.active_texture(glow::TEXTURE0);
+                gl.bind_texture(glow::TEXTURE_2D, Some(source_tex));
+                gl.uniform_1_i32(
+                    gl.get_uniform_location(pass.program, "sourceTex").as_ref(),
+                    0,
+                );
+                gl.active_texture(glow::TEXTURE1);
+                gl.bind_texture(glow::TEXTURE_2D, Some(prev));
+                gl.uniform_1_i32(gl.get_uniform_location(pass.program, "prevTex").as_ref(), 1);
+
+                gl.draw_arrays(glow::TRIANGLES, 0, 6);
+                prev = pass.tex;
+            }
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+}
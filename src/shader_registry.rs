@@ -0,0 +1,200 @@
+// Named GLSL snippets resolved via `#include "name"` directives, so shared
+// code (the dual-gradient energy kernel, the fullscreen-quad vertex shader)
+// can be written once and reused across the passthrough, energy, and
+// post-process shaders instead of being copy-pasted into each of them.
+use std::collections::HashMap;
+
+use glow::{HasContext as _, NativeProgram, NativeShader};
+
+/// The fullscreen triangle-pair vertex shader every pass in this demo draws
+/// with. `#include` it by name rather than compiling it from a literal in
+/// each module that needs it.
+pub const FULLSCREEN_QUAD_VERTEX: &str = r#"
+    const vec2 verts[6] = vec2[6](
+        vec2(-1.0, -1.0),
+        vec2(1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, 1.0),
+        vec2(-1.0, -1.0),
+        vec2(1.0, 1.0)
+    );
+    out vec2 vUV;
+    void main() {
+        gl_Position = vec4(verts[gl_VertexID], 0.0, 1.0);
+        vUV = (verts[gl_VertexID] + 1) / 2;
+    }
+"#;
+
+/// A one-line vertex source consisting of a single `#include`,
+/// for passes that don't need any code of their own beyond a shared snippet.
+pub const FULLSCREEN_QUAD_VERTEX_MAIN: &str = "#include \"fullscreen_quad_vertex\"";
+
+/// Squared RGB dual-gradient energy of the four texel neighbours around
+/// `uv`, the per-pixel energy `OriginalAlgo::calculate_energy_matrix`
+/// computes on the CPU.
+pub const DUAL_GRADIENT_ENERGY: &str = r#"
+    vec3 sampleRgb(sampler2D tex, vec2 uv) {
+        return texture(tex, uv).rgb;
+    }
+
+    float dualGradientEnergy(sampler2D tex, vec2 uv, vec2 texelSize) {
+        vec3 left = sampleRgb(tex, uv - vec2(texelSize.x, 0.0));
+        vec3 right = sampleRgb(tex, uv + vec2(texelSize.x, 0.0));
+        vec3 above = sampleRgb(tex, uv - vec2(0.0, texelSize.y));
+        vec3 below = sampleRgb(tex, uv + vec2(0.0, texelSize.y));
+
+        vec3 xDiff = (right - left) * 255.0;
+        vec3 yDiff = (below - above) * 255.0;
+        return dot(xDiff, xDiff) + dot(yDiff, yDiff);
+    }
+"#;
+
+/// Resolves `#include "name"` directives against a table of named snippets,
+/// injects the platform's GLSL version header, and compiles/links the
+/// result, caching the program by its pre-resolution source so repeated
+/// `compile` calls with the same sources reuse it.
+pub struct ShaderRegistry {
+    shader_version: &'static str,
+    snippets: HashMap<&'static str, &'static str>,
+    programs: HashMap<(String, String), NativeProgram>,
+}
+
+impl ShaderRegistry {
+    pub fn new() -> Self {
+        let shader_version = if cfg!(target_arch = "wasm32") {
+            "#version 300 es"
+        } else {
+            "#version 330"
+        };
+        Self {
+            shader_version,
+            snippets: HashMap::new(),
+            programs: HashMap::new(),
+        }
+    }
+
+    /// A registry pre-populated with this demo's shared snippets.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register("fullscreen_quad_vertex", FULLSCREEN_QUAD_VERTEX);
+        registry.register("dual_gradient_energy", DUAL_GRADIENT_ENERGY);
+        registry
+    }
+
+    /// Register a named snippet, available to any `#include "name"`
+    /// directive resolved after this call.
+    pub fn register(&mut self, name: &'static str, source: &'static str) {
+        self.snippets.insert(name, source);
+    }
+
+    fn resolve(&self, source: &str, stack: &mut Vec<&'static str>) -> Result<String, String> {
+        let mut out = String::new();
+        for line in source.lines() {
+            match line
+                .trim()
+                .strip_prefix("#include \"")
+                .and_then(|rest| rest.strip_suffix('"'))
+            {
+                Some(name) => {
+                    if stack.contains(&name) {
+                        return Err(format!("#include cycle at \"{name}\""));
+                    }
+                    let (name, snippet) = self
+                        .snippets
+                        .get_key_value(name)
+                        .ok_or_else(|| format!("unknown #include \"{name}\""))?;
+                    stack.push(name);
+                    out.push_str(&self.resolve(snippet, stack)?);
+                    stack.pop();
+                    out.push('\n');
+                }
+                None => {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Resolve `#include`s in `vertex_source`/`fragment_source` against the
+    /// registered snippets, inject the version header, and compile/link a
+    /// program, reusing a prior result if this exact source pair was
+    /// compiled before.
+    ///
+    /// Returns `Err` instead of panicking on an unresolved `#include` or a
+    /// GLSL compile/link failure, since this is reachable from a
+    /// user-picked shader preset file (see `ShaderPass::new`) and a typo in
+    /// that file shouldn't take down the whole app.
+    pub fn compile(
+        &mut self,
+        gl: &glow::Context,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> Result<NativeProgram, String> {
+        let key = (vertex_source.to_string(), fragment_source.to_string());
+        if let Some(&program) = self.programs.get(&key) {
+            return Ok(program);
+        }
+
+        let vertex_resolved = self.resolve(vertex_source, &mut Vec::new())?;
+        let fragment_resolved = self.resolve(fragment_source, &mut Vec::new())?;
+
+        let program = self.link(gl, &vertex_resolved, &fragment_resolved)?;
+        self.programs.insert(key, program);
+        Ok(program)
+    }
+
+    fn link(
+        &self,
+        gl: &glow::Context,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> Result<NativeProgram, String> {
+        unsafe {
+            let program = gl.create_program().expect("Failed to create program");
+            let shader_sources = [
+                (glow::VERTEX_SHADER, vertex_source),
+                (glow::FRAGMENT_SHADER, fragment_source),
+            ];
+            let mut shaders: Vec<NativeShader> = Vec::with_capacity(shader_sources.len());
+            for (shader_type, shader_source) in shader_sources {
+                let shader = gl
+                    .create_shader(shader_type)
+                    .expect("Cannot create shader");
+                gl.shader_source(
+                    shader,
+                    &format!("{}\n{}", self.shader_version, shader_source),
+                );
+                gl.compile_shader(shader);
+                if !gl.get_shader_compile_status(shader) {
+                    let log = gl.get_shader_info_log(shader);
+                    gl.delete_shader(shader);
+                    for shader in shaders {
+                        gl.delete_shader(shader);
+                    }
+                    gl.delete_program(program);
+                    return Err(format!("failed to compile {shader_type}: {log}"));
+                }
+                gl.attach_shader(program, shader);
+                shaders.push(shader);
+            }
+
+            gl.link_program(program);
+            if !gl.get_program_link_status(program) {
+                let log = gl.get_program_info_log(program);
+                for shader in shaders {
+                    gl.detach_shader(program, shader);
+                    gl.delete_shader(shader);
+                }
+                gl.delete_program(program);
+                return Err(log);
+            }
+            for shader in shaders {
+                gl.detach_shader(program, shader);
+                gl.delete_shader(shader);
+            }
+            Ok(program)
+        }
+    }
+}